@@ -6,25 +6,55 @@
  *
  */
 
-use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::borsh::BorshSerialize;
 use near_sdk::collections::UnorderedMap;
-use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, log, near_bindgen, AccountId};
+use near_sdk::{
+    env, ext_contract, log, near, AccountId, BorshStorageKey, Gas, PanicOnDefault, Promise,
+    PromiseError,
+};
 
 // Define the default message
 const DEFAULT_MESSAGE: &str = "Hello";
 
+// Upper bound on a single page of reports, to keep `values()` iteration cheap
+const MAX_PAGE_LIMIT: u64 = 100;
+
+// Gas attached to the cross-contract `get_reports` call during aggregation
+const AGGREGATE_GAS: Gas = Gas::from_tgas(15);
+
+// Default max byte length for each report field, before the owner tunes it
+const DEFAULT_MAX_FIELD_LEN: u64 = 1024;
+
+// Interface of the sibling report contracts we aggregate from; `ext_contract`
+// only consumes this as macro input, so the trait itself is never called directly
+#[allow(dead_code)]
+#[ext_contract(ext_reports)]
+trait ReportsApi {
+    fn get_reports(&self, from_index: u64, limit: u64) -> Vec<Report>;
+}
+
+// Storage key prefixes for the contract's persistent collections
+#[derive(BorshSerialize, BorshStorageKey)]
+#[borsh(crate = "near_sdk::borsh")]
+enum StorageKey {
+    Reports,
+}
+
 // Define the contract structure
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize)]
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
 pub struct Contract {
     message: String,
     reports: UnorderedMap<usize, Report>,
     owner: AccountId,
+    max_field_len: u64,
+    // monotonically increasing; unlike `reports.len()` this never shrinks when a
+    // report is deleted, so ids are never reused for a still-live report
+    next_id: usize,
 }
 
-#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
-#[serde(crate = "near_sdk::serde")]
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Report {
     id: usize,
     author: AccountId,
@@ -34,23 +64,54 @@ pub struct Report {
     word_appreciation: String,
 }
 
-// Define the default, which automatically initializes the contract
-impl Default for Contract {
-    fn default() -> Self {
+// NEP-297 events emitted on report lifecycle changes, for off-chain indexers
+#[near(event_json(standard = "daily_reports"))]
+#[allow(clippy::enum_variant_names)]
+enum ReportEvent {
+    #[event_version("1.0.0")]
+    ReportAdded { id: usize, author: AccountId },
+    #[event_version("1.0.0")]
+    ReportUpdated { id: usize, author: AccountId },
+    #[event_version("1.0.0")]
+    ReportDeleted { id: usize, author: AccountId },
+}
+
+// Implement the contract structure
+#[near]
+impl Contract {
+    // Initializes the contract with a deliberately chosen owner, rather than
+    // defaulting to whoever happens to touch state first
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
         Self {
             message: DEFAULT_MESSAGE.to_string(),
-            reports: UnorderedMap::new(b"reports".to_vec()),
-            owner: env::signer_account_id(),
+            reports: UnorderedMap::new(StorageKey::Reports),
+            owner,
+            max_field_len: DEFAULT_MAX_FIELD_LEN,
+            next_id: 0,
         }
     }
-}
 
-// Implement the contract structure
-#[near_bindgen]
-impl Contract {
+    // hand out the next unused report id and advance the counter
+    fn take_next_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    // owner-settable cap on each report field's byte length, so it can be tuned without redeploying
+    pub fn set_field_limits(&mut self, max_len: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only the owner can set field limits"
+        );
+        self.max_field_len = max_len;
+    }
+
     // Public method - returns the greeting saved, defaulting to DEFAULT_MESSAGE
     pub fn get_greeting(&self) -> String {
-        return self.message.clone();
+        self.message.clone()
     }
 
     // Public method - accepts a greeting, such as "howdy", and records it
@@ -68,24 +129,62 @@ impl Contract {
         blocker: String,
         word_appreciation: String,
     ) -> usize {
+        self.validate_report_fields(&done_today, &goal_tomorrow, &blocker, &word_appreciation);
+
         let report = Report {
-            id: self.reports.len() as usize,
-            author: env::signer_account_id(),
+            id: self.take_next_id(),
+            author: env::predecessor_account_id(),
             done_today,
             goal_tomorrow,
             blocker,
             word_appreciation,
         };
         self.reports.insert(&report.id, &report);
+        ReportEvent::ReportAdded {
+            id: report.id,
+            author: report.author.clone(),
+        }
+        .emit();
         report.id
     }
 
-    // get a report
-    pub fn get_report(&self, id: usize) -> Report {
-        self.reports.get(&id).unwrap()
+    // get a report, or None if it doesn't exist
+    pub fn get_report(&self, id: usize) -> Option<Report> {
+        self.reports.get(&id)
+    }
+
+    // get a page of reports, newest additions included, for frontend listing
+    pub fn get_reports(&self, from_index: u64, limit: u64) -> Vec<Report> {
+        Self::assert_limit(limit);
+        self.reports
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // get a page of reports authored by a specific account
+    pub fn get_reports_by_author(
+        &self,
+        author: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<Report> {
+        Self::assert_limit(limit);
+        self.reports
+            .values()
+            .filter(|report| report.author == author)
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // total number of reports stored, for pagination bounds
+    pub fn total_reports(&self) -> u64 {
+        self.reports.len()
     }
 
-    // update a report
+    // update a report; only the original author or the contract owner may do so
     pub fn update_report(
         &mut self,
         id: usize,
@@ -94,9 +193,17 @@ impl Contract {
         blocker: String,
         word_appreciation: String,
     ) {
+        let existing = self.reports.get(&id).expect("Report not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == existing.author || caller == self.owner,
+            "Only the report's author or the contract owner can update it"
+        );
+        self.validate_report_fields(&done_today, &goal_tomorrow, &blocker, &word_appreciation);
+
         let report = Report {
             id,
-            author: env::signer_account_id(),
+            author: existing.author,
             done_today,
             goal_tomorrow,
             blocker,
@@ -104,17 +211,129 @@ impl Contract {
         };
 
         self.reports.insert(&id, &report);
-        // self.reports.remove(&id);
-        // self.reports.insert(&id, &report);
+        ReportEvent::ReportUpdated {
+            id: report.id,
+            author: report.author.clone(),
+        }
+        .emit();
     }
 
-    // delete a report
+    // delete a report; only the original author or the contract owner may do so
     pub fn delete_report(&mut self, id: usize) {
-        // Check if current user is NOT author
-        let user = env::signer_account_id();
-        assert_eq!(user, self.owner, "Only author can delete post");
+        let existing = self.reports.get(&id).expect("Report not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == existing.author || caller == self.owner,
+            "Only the report's author or the contract owner can delete it"
+        );
 
-        self.reports.remove(&id);
+        if let Some(report) = self.reports.remove(&id) {
+            ReportEvent::ReportDeleted {
+                id: report.id,
+                author: report.author,
+            }
+            .emit();
+        }
+    }
+
+    // reject empty required fields and fields over the configured byte length
+    fn validate_report_fields(
+        &self,
+        done_today: &str,
+        goal_tomorrow: &str,
+        blocker: &str,
+        word_appreciation: &str,
+    ) {
+        if let Some(reason) =
+            self.invalid_report_field(done_today, goal_tomorrow, blocker, word_appreciation)
+        {
+            env::panic_str(&reason);
+        }
+    }
+
+    // same checks as `validate_report_fields`, but returns the failure reason
+    // instead of panicking, so callers that can't abort the whole transaction
+    // (e.g. the aggregation callback) can skip the offending report instead
+    fn invalid_report_field(
+        &self,
+        done_today: &str,
+        goal_tomorrow: &str,
+        blocker: &str,
+        word_appreciation: &str,
+    ) -> Option<String> {
+        if done_today.is_empty() {
+            return Some("done_today must not be empty".to_string());
+        }
+        if goal_tomorrow.is_empty() {
+            return Some("goal_tomorrow must not be empty".to_string());
+        }
+
+        let fields = [
+            ("done_today", done_today),
+            ("goal_tomorrow", goal_tomorrow),
+            ("blocker", blocker),
+            ("word_appreciation", word_appreciation),
+        ];
+        for (name, value) in fields {
+            if value.len() as u64 > self.max_field_len {
+                return Some(format!(
+                    "{} exceeds the max length of {} bytes",
+                    name, self.max_field_len
+                ));
+            }
+        }
+        None
+    }
+
+    // reject pagination limits above MAX_PAGE_LIMIT, to bound the gas cost of
+    // deserializing `UnorderedMap` values during iteration
+    fn assert_limit(limit: u64) {
+        if limit > MAX_PAGE_LIMIT {
+            env::panic_str(&format!("limit must not exceed {}", MAX_PAGE_LIMIT));
+        }
+    }
+
+    // pull a page of reports from a sibling team's report contract and merge them in
+    pub fn aggregate_from(&mut self, other: AccountId, from_index: u64, limit: u64) -> Promise {
+        ext_reports::ext(other)
+            .with_static_gas(AGGREGATE_GAS)
+            .get_reports(from_index, limit)
+            .then(Self::ext(env::current_account_id()).aggregate_callback())
+    }
+
+    #[private]
+    pub fn aggregate_callback(
+        &mut self,
+        #[callback_result] result: Result<Vec<Report>, PromiseError>,
+    ) {
+        let fetched = match result {
+            Ok(reports) => reports,
+            Err(_) => {
+                log!("Failed to aggregate reports from sibling contract");
+                return;
+            }
+        };
+
+        for mut report in fetched {
+            if let Some(reason) = self.invalid_report_field(
+                &report.done_today,
+                &report.goal_tomorrow,
+                &report.blocker,
+                &report.word_appreciation,
+            ) {
+                log!("Skipping aggregated report from {}: {}", report.author, reason);
+                continue;
+            }
+
+            let id = self.take_next_id();
+            report.id = id;
+            self.reports.insert(&id, &report);
+            ReportEvent::ReportAdded {
+                id: report.id,
+                author: report.author,
+            }
+            .emit();
+        }
     }
 }
 
@@ -125,24 +344,26 @@ impl Contract {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
 
     #[test]
     fn get_default_greeting() {
-        let contract = Contract::default();
+        let contract = Contract::new(env::signer_account_id());
         // this test did not call set_greeting so should return the default "Hello" greeting
         assert_eq!(contract.get_greeting(), "Hello".to_string());
     }
 
     #[test]
     fn set_then_get_greeting() {
-        let mut contract = Contract::default();
+        let mut contract = Contract::new(env::signer_account_id());
         contract.set_greeting("howdy".to_string());
         assert_eq!(contract.get_greeting(), "howdy".to_string());
     }
 
     #[test]
     fn add_report() {
-        let mut contract = Contract::default();
+        let mut contract = Contract::new(env::signer_account_id());
         let report_id = contract.add_report(
             "done today".to_string(),
             "goal tomorrow".to_string(),
@@ -155,16 +376,16 @@ mod tests {
 
     #[test]
     fn get_report() {
-        let mut contract = Contract::default();
+        let mut contract = Contract::new(env::signer_account_id());
         let report_id = contract.add_report(
             "done today".to_string(),
             "goal tomorrow".to_string(),
             "blocker".to_string(),
             "word appreciation".to_string(),
         );
-        let report = contract.get_report(report_id);
+        let report = contract.get_report(report_id).unwrap();
         assert_eq!(report.id, 0);
-        assert_eq!(report.author, env::signer_account_id());
+        assert_eq!(report.author, env::predecessor_account_id());
         assert_eq!(report.done_today, "done today".to_string());
         assert_eq!(report.goal_tomorrow, "goal tomorrow".to_string());
         assert_eq!(report.blocker, "blocker".to_string());
@@ -173,7 +394,7 @@ mod tests {
 
     #[test]
     fn update_report() {
-        let mut contract = Contract::default();
+        let mut contract = Contract::new(env::signer_account_id());
         let report_id = contract.add_report(
             "done today".to_string(),
             "goal tomorrow".to_string(),
@@ -188,9 +409,9 @@ mod tests {
             "updated blocker".to_string(),
             "updated word appreciation".to_string(),
         );
-        let report = contract.get_report(report_id);
+        let report = contract.get_report(report_id).unwrap();
         assert_eq!(report.id, 0);
-        assert_eq!(report.author, env::signer_account_id());
+        assert_eq!(report.author, env::predecessor_account_id());
         assert_eq!(report.done_today, "updated done today".to_string());
         assert_eq!(report.goal_tomorrow, "updated goal tomorrow".to_string());
         assert_eq!(report.blocker, "updated blocker".to_string());
@@ -200,9 +421,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_reports_paginated() {
+        let mut contract = Contract::new(env::signer_account_id());
+        for i in 0..3 {
+            contract.add_report(
+                format!("done {}", i),
+                "goal tomorrow".to_string(),
+                "blocker".to_string(),
+                "word appreciation".to_string(),
+            );
+        }
+        assert_eq!(contract.total_reports(), 3);
+        let page = contract.get_reports(1, 10);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].done_today, "done 1".to_string());
+    }
+
+    #[test]
+    fn get_reports_past_end_returns_empty() {
+        let mut contract = Contract::new(env::signer_account_id());
+        contract.add_report(
+            "done today".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+        assert_eq!(contract.get_reports(5, 10), Vec::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "limit must not exceed 100")]
+    fn get_reports_rejects_limit_over_max() {
+        let contract = Contract::new(env::signer_account_id());
+        contract.get_reports(0, 101);
+    }
+
+    #[test]
+    fn get_reports_by_author_filters() {
+        let mut contract = Contract::new(env::signer_account_id());
+        contract.add_report(
+            "done today".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+        let reports = contract.get_reports_by_author(env::predecessor_account_id(), 0, 10);
+        assert_eq!(reports.len(), 1);
+        let other: AccountId = "someone-else.testnet".parse().unwrap();
+        assert_eq!(contract.get_reports_by_author(other, 0, 10), Vec::new());
+    }
+
     #[test]
     fn delete_report() {
-        let mut contract = Contract::default();
+        let mut contract = Contract::new(env::signer_account_id());
         let report_id = contract.add_report(
             "done today".to_string(),
             "goal tomorrow".to_string(),
@@ -213,4 +485,123 @@ mod tests {
         contract.delete_report(report_id);
         assert_eq!(contract.reports.len(), 0);
     }
+
+    #[test]
+    fn add_report_does_not_reuse_id_after_delete() {
+        let mut contract = Contract::new(env::signer_account_id());
+        let first_id = contract.add_report(
+            "done today".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+        contract.delete_report(first_id);
+        let second_id = contract.add_report(
+            "done today again".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+        assert_ne!(first_id, second_id);
+        assert!(contract.get_report(first_id).is_none());
+    }
+
+    #[test]
+    fn get_report_missing_returns_none() {
+        let contract = Contract::new(env::signer_account_id());
+        assert_eq!(contract.get_report(42), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the report's author or the contract owner can update it")]
+    fn update_report_rejects_non_author() {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id("author.testnet".parse().unwrap());
+        testing_env!(context.build());
+        let mut contract = Contract::new("owner.testnet".parse().unwrap());
+        let report_id = contract.add_report(
+            "done today".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+
+        context.predecessor_account_id("intruder.testnet".parse().unwrap());
+        testing_env!(context.build());
+        contract.update_report(
+            report_id,
+            "hijacked".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the report's author or the contract owner can update it")]
+    fn update_report_rejects_relayed_call_from_malicious_contract() {
+        // A malicious contract M calls update_report on the author's behalf: the
+        // transaction signer is still the author, but the immediate caller (predecessor)
+        // is M, so the check must key off predecessor_account_id, not signer_account_id.
+        let mut context = VMContextBuilder::new();
+        context.signer_account_id("author.testnet".parse().unwrap());
+        context.predecessor_account_id("author.testnet".parse().unwrap());
+        testing_env!(context.build());
+        let mut contract = Contract::new("owner.testnet".parse().unwrap());
+        let report_id = contract.add_report(
+            "done today".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+
+        context.signer_account_id("author.testnet".parse().unwrap());
+        context.predecessor_account_id("malicious.testnet".parse().unwrap());
+        testing_env!(context.build());
+        contract.update_report(
+            report_id,
+            "hijacked".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "done_today must not be empty")]
+    fn add_report_rejects_empty_done_today() {
+        let mut contract = Contract::new(env::signer_account_id());
+        contract.add_report(
+            "".to_string(),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the max length")]
+    fn add_report_rejects_oversized_field() {
+        let mut contract = Contract::new(env::signer_account_id());
+        contract.add_report(
+            "a".repeat(DEFAULT_MAX_FIELD_LEN as usize + 1),
+            "goal tomorrow".to_string(),
+            "blocker".to_string(),
+            "word appreciation".to_string(),
+        );
+    }
+
+    #[test]
+    fn set_field_limits_changes_the_cap() {
+        let mut contract = Contract::new(env::signer_account_id());
+        contract.set_field_limits(4);
+        assert_eq!(contract.max_field_len, 4);
+        let report_id = contract.add_report(
+            "ok".to_string(),
+            "fine".to_string(),
+            "".to_string(),
+            "".to_string(),
+        );
+        assert_eq!(report_id, 0);
+    }
 }